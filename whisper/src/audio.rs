@@ -5,6 +5,21 @@ pub const CHUNK_SECONDS: usize = 10;
 pub const SAMPLES_PER_CHUNK: usize = SAMPLE_RATE_HZ * CHUNK_SECONDS;
 pub const BYTES_PER_SAMPLE: usize = 2;
 
+/// Frame size for VAD analysis: 30 ms at 16 kHz.
+const VAD_FRAME_MS: usize = 30;
+const VAD_FRAME_SAMPLES: usize = SAMPLE_RATE_HZ * VAD_FRAME_MS / 1000;
+/// Window used to estimate the initial noise floor before any speech arrives.
+const VAD_NOISE_ESTIMATE_MS: usize = 300;
+const VAD_NOISE_ESTIMATE_FRAMES: usize = VAD_NOISE_ESTIMATE_MS / VAD_FRAME_MS;
+/// Margin applied on top of the noise floor to decide a frame is speech.
+const VAD_FLOOR_MARGIN: f32 = 3.0;
+/// Trailing frames kept active after the last frame classified as speech.
+const VAD_HANGOVER_FRAMES: usize = 5;
+/// Minimum duration (ms) a voiced island must span to survive the island filter,
+/// counted in whole frames rounded down (3 frames, i.e. 90 ms, at 30 ms frames).
+const VAD_MIN_SPEECH_MS: usize = 100;
+const VAD_MIN_SPEECH_FRAMES: usize = VAD_MIN_SPEECH_MS / VAD_FRAME_MS;
+
 pub fn load_pcm_i16le_bytes(bytes: &[u8]) -> Result<Vec<f32>> {
     if bytes.is_empty() {
         return Err(anyhow!("Empty audio payload"));
@@ -27,6 +42,327 @@ pub fn silent_chunk() -> Vec<f32> {
     vec![0.0; SAMPLES_PER_CHUNK]
 }
 
+fn frame_energy(frame: &[f32]) -> f32 {
+    frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32
+}
+
+/// Frame-based energy voice activity detector.
+///
+/// Splits `samples` into `VAD_FRAME_SAMPLES`-sized frames, estimates an
+/// adaptive noise floor from the first `VAD_NOISE_ESTIMATE_MS` of audio, and
+/// flags frames whose energy clears `floor * VAD_FLOOR_MARGIN` as speech.
+/// Hangover smoothing keeps a few trailing frames active past the last
+/// speech frame so plosives and trailing consonants aren't chopped, and a
+/// minimum-speech-duration filter drops islands shorter than
+/// `VAD_MIN_SPEECH_FRAMES`. The filter runs after hangover smoothing, so an
+/// island's length includes its hangover frames. Returns merged
+/// `(start, end)` sample ranges.
+///
+/// Energy-only, deliberately: an earlier version also gated on zero-crossing
+/// rate, but the ZCR band was rejecting valid speech and was dropped rather
+/// than tuned.
+pub fn detect_voiced_regions(samples: &[f32]) -> Vec<(usize, usize)> {
+    detect_voiced_regions_seeded(samples, None).0
+}
+
+/// Like [`detect_voiced_regions`], but seeds the noise floor from `seed_floor`
+/// instead of re-estimating it from `samples`' own first `VAD_NOISE_ESTIMATE_MS`
+/// when given — needed when `samples` is a retained window tail whose head is
+/// mid-utterance rather than true silence. Returns the spans plus the floor
+/// at the end of `samples`, to seed the next call over the same utterance.
+pub fn detect_voiced_regions_seeded(samples: &[f32], seed_floor: Option<f32>) -> (Vec<(usize, usize)>, f32) {
+    if samples.is_empty() {
+        return (Vec::new(), seed_floor.unwrap_or(0.0));
+    }
+
+    let frames: Vec<&[f32]> = samples.chunks(VAD_FRAME_SAMPLES).collect();
+    let energies: Vec<f32> = frames.iter().map(|f| frame_energy(f)).collect();
+
+    let mut noise_floor = match seed_floor {
+        Some(floor) => floor,
+        None => {
+            let estimate_frames = VAD_NOISE_ESTIMATE_FRAMES.min(energies.len()).max(1);
+            let floor = energies[..estimate_frames]
+                .iter()
+                .cloned()
+                .fold(f32::INFINITY, f32::min);
+            if floor.is_finite() {
+                floor
+            } else {
+                0.0
+            }
+        }
+    };
+
+    // A frame counts as speech when it clears the energy floor; the floor
+    // only ratchets downward on non-speech frames and the threshold is
+    // recomputed from it every frame, so it keeps tracking quieter stretches.
+    let mut is_speech = vec![false; frames.len()];
+    for i in 0..frames.len() {
+        let energy = energies[i];
+        let threshold = noise_floor * VAD_FLOOR_MARGIN;
+        if energy > threshold {
+            is_speech[i] = true;
+        } else {
+            noise_floor = noise_floor.min(energy);
+        }
+    }
+
+    // Hangover smoothing: extend each speech frame forward by a few frames.
+    let mut active = is_speech.clone();
+    let mut hangover = 0usize;
+    for i in 0..active.len() {
+        if is_speech[i] {
+            hangover = VAD_HANGOVER_FRAMES;
+        } else if hangover > 0 {
+            active[i] = true;
+            hangover -= 1;
+        }
+    }
+
+    // Merge contiguous active frames into spans and drop short islands.
+    let mut spans = Vec::new();
+    let mut span_start: Option<usize> = None;
+    for (i, &flag) in active.iter().enumerate() {
+        match (flag, span_start) {
+            (true, None) => span_start = Some(i),
+            (false, Some(start)) => {
+                if i - start >= VAD_MIN_SPEECH_FRAMES {
+                    spans.push((start, i));
+                }
+                span_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = span_start {
+        if active.len() - start >= VAD_MIN_SPEECH_FRAMES {
+            spans.push((start, active.len()));
+        }
+    }
+
+    let spans = spans
+        .into_iter()
+        .map(|(start, end)| {
+            let start_sample = start * VAD_FRAME_SAMPLES;
+            let end_sample = (end * VAD_FRAME_SAMPLES).min(samples.len());
+            (start_sample, end_sample)
+        })
+        .collect();
+
+    (spans, noise_floor)
+}
+
+/// Merges [`detect_voiced_regions`] spans, splitting wherever the gap
+/// between two voiced spans exceeds `split_silence_secs`. Each returned
+/// `(start, end)` range still contains its own internal pauses verbatim;
+/// only gaps longer than the threshold are cut.
+pub fn split_spans_on_silence(samples: &[f32], split_silence_secs: f32) -> Vec<(usize, usize)> {
+    let spans = detect_voiced_regions(samples);
+    if spans.is_empty() {
+        return Vec::new();
+    }
+
+    let split_gap_samples = (split_silence_secs * SAMPLE_RATE_HZ as f32) as usize;
+
+    let mut merged = Vec::new();
+    let mut span_start = spans[0].0;
+    let mut span_end = spans[0].1;
+    for &(start, end) in &spans[1..] {
+        if start.saturating_sub(span_end) > split_gap_samples {
+            merged.push((span_start, span_end));
+            span_start = start;
+        }
+        span_end = end;
+    }
+    merged.push((span_start, span_end));
+    merged
+}
+
+/// Trims leading/trailing silence and splits `samples` into separate chunks
+/// per [`split_spans_on_silence`].
+pub fn split_on_silence(samples: &[f32], split_silence_secs: f32) -> Vec<Vec<f32>> {
+    split_spans_on_silence(samples, split_silence_secs)
+        .into_iter()
+        .map(|(start, end)| samples[start..end].to_vec())
+        .collect()
+}
+
+struct WavFormat {
+    format_tag: u16,
+    channels: usize,
+    sample_rate: usize,
+    bits_per_sample: u16,
+}
+
+const WAV_FORMAT_PCM: u16 = 1;
+const WAV_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// Parses a RIFF/WAVE container and returns `(mono_samples, sample_rate)`.
+/// Supports PCM 16-bit and IEEE float sample formats; stereo (or wider)
+/// input is downmixed to mono by averaging channels.
+pub fn parse_wav(bytes: &[u8]) -> Result<(Vec<f32>, usize)> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(anyhow!("Not a RIFF/WAVE file"));
+    }
+
+    let mut pos = 12;
+    let mut fmt: Option<WavFormat> = None;
+    let mut data: Option<&[u8]> = None;
+
+    while pos + 8 <= bytes.len() {
+        let chunk_id = &bytes[pos..pos + 4];
+        let chunk_size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let chunk_start = pos + 8;
+        let chunk_end = (chunk_start + chunk_size).min(bytes.len());
+
+        match chunk_id {
+            b"fmt " if chunk_end - chunk_start >= 16 => {
+                fmt = Some(WavFormat {
+                    format_tag: u16::from_le_bytes(bytes[chunk_start..chunk_start + 2].try_into().unwrap()),
+                    channels: u16::from_le_bytes(bytes[chunk_start + 2..chunk_start + 4].try_into().unwrap())
+                        as usize,
+                    sample_rate: u32::from_le_bytes(bytes[chunk_start + 4..chunk_start + 8].try_into().unwrap())
+                        as usize,
+                    bits_per_sample: u16::from_le_bytes(
+                        bytes[chunk_start + 14..chunk_start + 16].try_into().unwrap(),
+                    ),
+                });
+            }
+            b"data" => data = Some(&bytes[chunk_start..chunk_end]),
+            _ => {}
+        }
+
+        // Chunks are word-aligned: a chunk with an odd size has a pad byte.
+        pos = chunk_end + (chunk_size % 2);
+    }
+
+    let fmt = fmt.ok_or_else(|| anyhow!("WAV file is missing a fmt chunk"))?;
+    let data = data.ok_or_else(|| anyhow!("WAV file is missing a data chunk"))?;
+    if fmt.channels == 0 {
+        return Err(anyhow!("WAV fmt chunk declares zero channels"));
+    }
+    if fmt.sample_rate == 0 {
+        return Err(anyhow!("WAV fmt chunk declares a zero sample rate"));
+    }
+
+    let interleaved = decode_wav_samples(data, &fmt)?;
+    Ok((downmix_to_mono(&interleaved, fmt.channels), fmt.sample_rate))
+}
+
+fn decode_wav_samples(data: &[u8], fmt: &WavFormat) -> Result<Vec<f32>> {
+    match (fmt.format_tag, fmt.bits_per_sample) {
+        (WAV_FORMAT_PCM, 16) => Ok(data
+            .chunks_exact(2)
+            .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / i16::MAX as f32)
+            .collect()),
+        (WAV_FORMAT_IEEE_FLOAT, 32) => Ok(data
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()),
+        (tag, bits) => Err(anyhow!(
+            "Unsupported WAV sample format (format tag {tag}, {bits}-bit)"
+        )),
+    }
+}
+
+fn downmix_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Half-width of the windowed-sinc lowpass kernel, in sinc zero crossings:
+/// the kernel spans `SINC_HALF_TAPS * max(L, M)` taps of the upsampled signal
+/// on each side, i.e. ~16 input samples per output sample when upsampling.
+const SINC_HALF_TAPS: usize = 8;
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        1.0
+    } else {
+        (std::f32::consts::PI * x).sin() / (std::f32::consts::PI * x)
+    }
+}
+
+/// Hann-windowed sinc lowpass kernel with the given normalized cutoff, in
+/// cycles per sample of the signal it will be applied to (0.5 is Nyquist).
+fn design_lowpass_kernel(cutoff: f32, half_taps: usize) -> Vec<f32> {
+    let n = 2 * half_taps + 1;
+    let kernel: Vec<f32> = (0..n)
+        .map(|i| {
+            let x = i as isize - half_taps as isize;
+            let ideal = 2.0 * cutoff * sinc(2.0 * cutoff * x as f32);
+            let window = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (n - 1) as f32).cos();
+            ideal * window
+        })
+        .collect();
+    let gain: f32 = kernel.iter().sum();
+    kernel.iter().map(|k| k / gain).collect()
+}
+
+/// Evaluates one sample of the upsample-by-L / lowpass chain at
+/// `upsampled_index` without ever materializing the zero-stuffed signal.
+/// The zero-stuffed signal is nonzero only at multiples of `factor`, where
+/// its value is `samples[i / factor] * factor` (the `factor` scaling
+/// restores the energy lost by interleaving zeros), so the convolution sum
+/// only has a non-zero term every `factor`-th tap; only those taps are visited.
+fn polyphase_sample(samples: &[f32], kernel: &[f32], factor: usize, upsampled_index: usize, upsampled_len: usize) -> f32 {
+    let half = kernel.len() / 2;
+    // Input samples whose zero-stuffed position `j * factor` is in the kernel's reach.
+    let first = upsampled_index.saturating_sub(half).div_ceil(factor);
+    let last = (upsampled_index + half).min(upsampled_len - 1) / factor;
+    (first..=last)
+        .map(|j| samples[j] * factor as f32 * kernel[j * factor + half - upsampled_index])
+        .sum()
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Resamples `samples` from `src_rate` to [`SAMPLE_RATE_HZ`] using a
+/// rational upsample-by-L / lowpass / decimate-by-M polyphase chain, where
+/// `L / M` is `SAMPLE_RATE_HZ / src_rate` reduced via GCD. The lowpass is a
+/// Hann-windowed sinc filter sized to the tighter of the two Nyquist rates,
+/// which also suppresses the imaging/aliasing introduced by the zero-stuff
+/// upsample and the decimation respectively.
+///
+/// Each output sample is computed directly via [`polyphase_sample`] instead
+/// of expanding the full zero-stuffed signal: for consumer rates like
+/// 44.1 kHz/22.05 kHz, `L` alone is in the hundreds, so a few minutes of
+/// audio would otherwise blow up to gigabytes of zero-stuffed buffer before
+/// the filter even runs.
+pub fn resample_to_16k(samples: &[f32], src_rate: usize) -> Vec<f32> {
+    if samples.is_empty() || src_rate == SAMPLE_RATE_HZ {
+        return samples.to_vec();
+    }
+
+    let divisor = gcd(src_rate, SAMPLE_RATE_HZ);
+    let upsample_factor = SAMPLE_RATE_HZ / divisor;
+    let downsample_factor = src_rate / divisor;
+
+    let cutoff = 0.5 / upsample_factor.max(downsample_factor) as f32;
+    let kernel = design_lowpass_kernel(cutoff, SINC_HALF_TAPS * upsample_factor.max(downsample_factor));
+
+    let upsampled_len = samples.len() * upsample_factor;
+    let out_len = upsampled_len.div_ceil(downsample_factor);
+    (0..out_len)
+        .map(|n| {
+            let upsampled_index = n * downsample_factor;
+            polyphase_sample(samples, &kernel, upsample_factor, upsampled_index, upsampled_len)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +381,145 @@ mod tests {
         let err = load_pcm_i16le_bytes(&bytes).unwrap_err();
         assert!(err.to_string().contains("even"));
     }
+
+    fn tone(freq_hz: f32, amplitude: f32, duration_secs: f32) -> Vec<f32> {
+        let n = (SAMPLE_RATE_HZ as f32 * duration_secs) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE_HZ as f32;
+                amplitude * (2.0 * std::f32::consts::PI * freq_hz * t).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detect_voiced_regions_ignores_silence() {
+        let samples = silent_chunk();
+        assert!(detect_voiced_regions(&samples).is_empty());
+    }
+
+    #[test]
+    fn detect_voiced_regions_finds_a_tone_between_silence() {
+        let mut samples = vec![0.0; SAMPLE_RATE_HZ / 2];
+        samples.extend(tone(220.0, 0.8, 1.0));
+        samples.extend(vec![0.0; SAMPLE_RATE_HZ / 2]);
+
+        let spans = detect_voiced_regions(&samples);
+        assert!(!spans.is_empty());
+        let (start, end) = spans[0];
+        assert!(start > 0 && end < samples.len());
+    }
+
+    #[test]
+    fn split_on_silence_splits_distant_spans() {
+        // Leading silence so the noise floor is estimated from silence, not speech.
+        let mut samples = vec![0.0; SAMPLE_RATE_HZ / 2];
+        samples.extend(tone(220.0, 0.8, 0.5));
+        samples.extend(vec![0.0; SAMPLE_RATE_HZ * 2]);
+        samples.extend(tone(220.0, 0.8, 0.5));
+
+        let chunks = split_on_silence(&samples, 1.0);
+        assert_eq!(chunks.len(), 2);
+    }
+
+    fn build_wav_pcm16(sample_rate: u32, channels: u16, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let byte_rate = sample_rate * channels as u32 * 2;
+        let block_align = channels * 2;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&WAV_FORMAT_PCM.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&byte_rate.to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    #[test]
+    fn parse_wav_reads_mono_pcm16() {
+        let wav = build_wav_pcm16(16_000, 1, &[0, i16::MAX / 2, i16::MIN / 2]);
+        let (samples, sample_rate) = parse_wav(&wav).unwrap();
+        assert_eq!(sample_rate, 16_000);
+        assert_eq!(samples.len(), 3);
+        assert!((samples[1] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn parse_wav_downmixes_stereo() {
+        // Left channel at +1.0, right channel at -1.0 for every frame.
+        let wav = build_wav_pcm16(8_000, 2, &[i16::MAX, i16::MIN, i16::MAX, i16::MIN]);
+        let (samples, sample_rate) = parse_wav(&wav).unwrap();
+        assert_eq!(sample_rate, 8_000);
+        assert_eq!(samples.len(), 2);
+        assert!(samples.iter().all(|s| s.abs() < 1e-2));
+    }
+
+    #[test]
+    fn parse_wav_rejects_non_riff_input() {
+        let err = parse_wav(&[0u8; 16]).unwrap_err();
+        assert!(err.to_string().contains("RIFF"));
+    }
+
+    #[test]
+    fn parse_wav_rejects_zero_sample_rate() {
+        let wav = build_wav_pcm16(0, 1, &[0, i16::MAX / 2]);
+        let err = parse_wav(&wav).unwrap_err();
+        assert!(err.to_string().contains("sample rate"));
+    }
+
+    #[test]
+    fn resample_to_16k_is_noop_at_target_rate() {
+        let samples = tone(220.0, 0.5, 0.1);
+        let resampled = resample_to_16k(&samples, SAMPLE_RATE_HZ);
+        assert_eq!(resampled, samples);
+    }
+
+    #[test]
+    fn resample_to_16k_scales_sample_count_with_ratio() {
+        let samples = tone(220.0, 0.5, 1.0);
+        let resampled = resample_to_16k(&samples, 8_000);
+        // 8 kHz -> 16 kHz doubles the sample count (1:2 ratio).
+        assert_eq!(resampled.len(), samples.len() * 2);
+    }
+
+    fn tone_at_rate(sample_rate: usize, freq_hz: f32, amplitude: f32, duration_secs: f32) -> Vec<f32> {
+        let n = (sample_rate as f32 * duration_secs) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                amplitude * (2.0 * std::f32::consts::PI * freq_hz * t).sin()
+            })
+            .collect()
+    }
+
+    // L/M for 44.1 kHz and 22.05 kHz are 160/441 and 320/441: the biggest L
+    // of the rates this module supports. A minute of audio at these rates
+    // used to expand into a multi-gigabyte zero-stuffed buffer before the
+    // polyphase rewrite; this exercises that path end to end and checks the
+    // output length instead of just the trivial 8 kHz (L=2) ratio.
+    #[test]
+    fn resample_to_16k_handles_44100hz_voice_memo_length() {
+        let samples = tone_at_rate(44_100, 220.0, 0.5, 60.0);
+        let resampled = resample_to_16k(&samples, 44_100);
+        let expected = (samples.len() as f32 * SAMPLE_RATE_HZ as f32 / 44_100.0).round() as usize;
+        assert!(resampled.len().abs_diff(expected) <= 1);
+    }
+
+    #[test]
+    fn resample_to_16k_handles_22050hz_voice_memo_length() {
+        let samples = tone_at_rate(22_050, 220.0, 0.5, 60.0);
+        let resampled = resample_to_16k(&samples, 22_050);
+        let expected = (samples.len() as f32 * SAMPLE_RATE_HZ as f32 / 22_050.0).round() as usize;
+        assert!(resampled.len().abs_diff(expected) <= 1);
+    }
 }
@@ -20,6 +20,17 @@ pub struct Cli {
     /// Max number of queued transcription jobs
     #[arg(long, default_value_t = 8)]
     pub queue_capacity: usize,
+    /// Voice activity detection mode applied before inference
+    #[arg(long, value_enum, default_value = "off")]
+    pub vad: VadMode,
+    /// Silence gap (seconds) between voiced spans that triggers a split into
+    /// separate transcription calls
+    #[arg(long = "vad-split-silence", default_value_t = 1.0)]
+    pub vad_split_silence: f32,
+    /// Number of worker threads pulling from the shared transcription
+    /// queue, each with its own loaded model
+    #[arg(long, default_value_t = 1)]
+    pub workers: usize,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
@@ -36,3 +47,9 @@ impl EngineKind {
         }
     }
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum VadMode {
+    Off,
+    Energy,
+}
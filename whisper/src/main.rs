@@ -20,6 +20,9 @@ async fn main() -> Result<()> {
         bind: cli.bind.clone(),
         max_bytes: cli.max_bytes,
         queue_capacity: cli.queue_capacity,
+        vad: cli.vad,
+        vad_split_silence: cli.vad_split_silence,
+        workers: cli.workers,
     };
     server::run(config).await?;
     Ok(())
@@ -1,22 +1,30 @@
 use crate::audio;
-use crate::cli::EngineKind;
-use crate::transcriber::Transcriber;
+use crate::cli::{EngineKind, VadMode};
+use crate::transcriber::{join_segment_text, Segment, Transcriber, Transcription, TranscriptionOptions};
 use anyhow::{anyhow, Result};
 use axum::{
     body::Bytes,
-    extract::State,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Multipart, Query, State,
+    },
     http::{header, HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
 use log::{info, warn};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::{fmt, fmt::Display};
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::oneshot;
 use tower_http::limit::RequestBodyLimitLayer;
 
+/// Overlap kept at the head of the next streaming window so words spoken
+/// right at a window boundary aren't cut in half.
+const STREAM_WINDOW_OVERLAP_SECONDS: usize = 1;
+
 #[derive(Clone, Debug)]
 pub struct ServerConfig {
     pub engine: EngineKind,
@@ -24,45 +32,74 @@ pub struct ServerConfig {
     pub bind: String,
     pub max_bytes: usize,
     pub queue_capacity: usize,
+    pub vad: VadMode,
+    pub vad_split_silence: f32,
+    pub workers: usize,
 }
 
 #[derive(Clone)]
 struct AppState {
     worker: WorkerClient,
     config: Arc<ServerConfig>,
+    ready_workers: Arc<AtomicUsize>,
 }
 
 #[derive(Debug)]
 struct Job {
     audio: Vec<f32>,
-    reply: oneshot::Sender<Result<String, String>>,
+    options: TranscriptionOptions,
+    reply: oneshot::Sender<Result<Transcription, String>>,
 }
 
+/// `/transcribe` batch jobs and `/stream` streaming windows are queued on
+/// separate multi-consumer channels so a burst of streaming windows can't
+/// starve batch requests; every worker in the pool drains the batch channel
+/// with priority over the stream channel. `async_channel` (rather than
+/// `tokio::sync::mpsc`) is used because its receivers are `Clone`, letting
+/// every worker thread pull from the same queue concurrently.
 #[derive(Clone)]
 struct WorkerClient {
-    sender: mpsc::Sender<Job>,
+    batch_sender: async_channel::Sender<Job>,
+    stream_sender: async_channel::Sender<Job>,
 }
 
 impl WorkerClient {
-    fn new(sender: mpsc::Sender<Job>) -> Self {
-        Self { sender }
+    fn new(batch_sender: async_channel::Sender<Job>, stream_sender: async_channel::Sender<Job>) -> Self {
+        Self {
+            batch_sender,
+            stream_sender,
+        }
     }
 
     async fn transcribe(
         &self,
         audio: Vec<f32>,
-    ) -> Result<String, WorkerError> {
+        options: TranscriptionOptions,
+    ) -> Result<Transcription, WorkerError> {
+        Self::submit(&self.batch_sender, audio, options).await
+    }
+
+    async fn transcribe_stream(&self, audio: Vec<f32>) -> Result<Transcription, WorkerError> {
+        Self::submit(&self.stream_sender, audio, TranscriptionOptions::default()).await
+    }
+
+    async fn submit(
+        sender: &async_channel::Sender<Job>,
+        audio: Vec<f32>,
+        options: TranscriptionOptions,
+    ) -> Result<Transcription, WorkerError> {
         let (reply_tx, reply_rx) = oneshot::channel();
         let job = Job {
             audio,
+            options,
             reply: reply_tx,
         };
 
-        self.sender
+        sender
             .try_send(job)
             .map_err(|err| match err {
-                mpsc::error::TrySendError::Closed(_) => WorkerError::WorkerGone,
-                mpsc::error::TrySendError::Full(_) => WorkerError::QueueFull,
+                async_channel::TrySendError::Closed(_) => WorkerError::WorkerGone,
+                async_channel::TrySendError::Full(_) => WorkerError::QueueFull,
             })?;
 
         match reply_rx.await {
@@ -103,6 +140,29 @@ impl Display for WorkerError {
 #[derive(Debug, Serialize)]
 struct TranscribeResponse {
     text: String,
+    language: Option<String>,
+}
+
+/// Query-string decoding parameters accepted by `/transcribe` and
+/// `/v1/audio/transcriptions`.
+#[derive(Debug, Deserialize)]
+struct TranscribeParams {
+    /// ISO-639-1 code, or `"auto"` to let Whisper detect it.
+    language: Option<String>,
+    translate: Option<bool>,
+    temperature: Option<f32>,
+    beam_size: Option<usize>,
+}
+
+impl TranscribeParams {
+    fn into_options(self) -> TranscriptionOptions {
+        TranscriptionOptions {
+            language: self.language.filter(|lang| lang != "auto"),
+            translate: self.translate.unwrap_or(false),
+            temperature: self.temperature.unwrap_or(0.0),
+            beam_size: self.beam_size.unwrap_or(1).max(1),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -110,22 +170,56 @@ struct HealthResponse {
     status: &'static str,
     engine: String,
     model_path: String,
+    workers: usize,
+    ready_workers: usize,
 }
 
 pub async fn run(config: ServerConfig) -> Result<()> {
     let bind_addr = config.bind.clone();
     let max_bytes = config.max_bytes;
-    let (tx, rx) = mpsc::channel::<Job>(config.queue_capacity);
-    spawn_worker(config.engine, &config.model_path, rx)?;
+    let workers = config.workers.max(1);
+    let (batch_tx, batch_rx) = async_channel::bounded::<Job>(config.queue_capacity);
+    let (stream_tx, stream_rx) = async_channel::bounded::<Job>(config.queue_capacity);
+    let ready_workers = Arc::new(AtomicUsize::new(0));
+    let load_acks = spawn_worker_pool(
+        config.engine,
+        &config.model_path,
+        config.vad,
+        config.vad_split_silence,
+        workers,
+        batch_rx,
+        stream_rx,
+        ready_workers.clone(),
+    )?;
+    // Wait for every worker to finish its load attempt (success or failure)
+    // before accepting connections: a job submitted before any worker is
+    // ready would sit in the channel with no one left to drain it, leaving
+    // the request hanging forever instead of failing fast like the old
+    // synchronous single-worker load did.
+    for ack in load_acks {
+        let _ = ack.await;
+    }
+    if ready_workers.load(Ordering::SeqCst) == 0 {
+        return Err(anyhow!(
+            "No worker could load the model at {}; refusing to start",
+            config.model_path
+        ));
+    }
 
     let state = AppState {
-        worker: WorkerClient::new(tx),
+        worker: WorkerClient::new(batch_tx, stream_tx),
         config: Arc::new(config),
+        ready_workers,
     };
 
     let app = Router::new()
         .route("/health", get(health_handler))
         .route("/transcribe", post(transcribe_handler))
+        .route("/stream", get(stream_handler))
+        .route(
+            "/v1/audio/transcriptions",
+            post(openai_transcriptions_handler),
+        )
         .layer(RequestBodyLimitLayer::new(max_bytes))
         .with_state(state);
 
@@ -135,64 +229,195 @@ pub async fn run(config: ServerConfig) -> Result<()> {
     Ok(())
 }
 
-fn spawn_worker(engine: EngineKind, model_path: &str, rx: mpsc::Receiver<Job>) -> Result<()> {
-    let mut transcriber = Transcriber::new(engine, model_path)?;
-    info!("Loaded model for engine {} from {}", engine.as_str(), model_path);
-    let warmup = audio::silent_chunk();
-    if let Err(err) = transcriber.transcribe(warmup) {
-        warn!("Warm-up inference failed: {}", err);
+/// Spawns `workers` threads, each loading and warming up its own
+/// `Transcriber` independently (Whisper/Parakeet engine state isn't
+/// `Send`-shareable across threads) and then pulling jobs from the shared
+/// `batch_rx`/`stream_rx` queues. `ready_workers` is incremented once a
+/// worker finishes loading and warming up, so `/health` can report how many
+/// workers are ready.
+///
+/// Returns one oneshot receiver per worker that resolves once that worker's
+/// load attempt (success or failure) completes. The caller awaits all of
+/// them before accepting connections, so a model path bad enough to fail
+/// every worker is caught at startup instead of silently queuing requests
+/// no worker will ever drain.
+fn spawn_worker_pool(
+    engine: EngineKind,
+    model_path: &str,
+    vad: VadMode,
+    vad_split_silence: f32,
+    workers: usize,
+    batch_rx: async_channel::Receiver<Job>,
+    stream_rx: async_channel::Receiver<Job>,
+    ready_workers: Arc<AtomicUsize>,
+) -> Result<Vec<oneshot::Receiver<()>>> {
+    let mut load_acks = Vec::with_capacity(workers);
+    for worker_id in 0..workers {
+        let model_path = model_path.to_string();
+        let batch_rx = batch_rx.clone();
+        let stream_rx = stream_rx.clone();
+        let ready_workers = ready_workers.clone();
+        let (load_ack_tx, load_ack_rx) = oneshot::channel();
+        load_acks.push(load_ack_rx);
+        std::thread::Builder::new()
+            .name(format!("transcription-worker-{worker_id}"))
+            .spawn(move || {
+                let mut transcriber = match Transcriber::new(engine, &model_path) {
+                    Ok(transcriber) => transcriber,
+                    Err(err) => {
+                        warn!("Worker {worker_id} failed to load model: {err}");
+                        let _ = load_ack_tx.send(());
+                        return;
+                    }
+                };
+                info!(
+                    "Worker {worker_id} loaded model for engine {} from {}",
+                    engine.as_str(),
+                    model_path
+                );
+                let warmup = audio::silent_chunk();
+                if let Err(err) = transcriber.transcribe(warmup, &TranscriptionOptions::default()) {
+                    warn!("Worker {worker_id} warm-up inference failed: {err}");
+                }
+                ready_workers.fetch_add(1, Ordering::SeqCst);
+                info!("Worker {worker_id} ready");
+                let _ = load_ack_tx.send(());
+
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .expect("failed to start worker runtime");
+                runtime.block_on(worker_loop(
+                    &mut transcriber,
+                    batch_rx,
+                    stream_rx,
+                    vad,
+                    vad_split_silence,
+                ));
+            })
+            .map_err(|e| anyhow!("Failed to spawn worker thread {worker_id}: {e}"))?;
     }
-    std::thread::Builder::new()
-        .name("transcription-worker".to_string())
-        .spawn(move || worker_loop(&mut transcriber, rx))
-        .map_err(|e| anyhow!("Failed to spawn worker thread: {e}"))?;
-    info!("Transcription worker ready");
-    Ok(())
+    Ok(load_acks)
 }
 
-fn worker_loop(transcriber: &mut Transcriber, mut rx: mpsc::Receiver<Job>) {
-    while let Some(job) = rx.blocking_recv() {
-        let result = transcriber
-            .transcribe(job.audio)
-            .map_err(|err| err.to_string());
+/// Picks the next job, preferring the batch queue over the streaming queue
+/// so bursts of `/stream` windows can't starve `/transcribe` requests.
+enum NextJob {
+    Batch(Result<Job, async_channel::RecvError>),
+    Stream(Result<Job, async_channel::RecvError>),
+}
+
+async fn worker_loop(
+    transcriber: &mut Transcriber,
+    batch_rx: async_channel::Receiver<Job>,
+    stream_rx: async_channel::Receiver<Job>,
+    vad: VadMode,
+    vad_split_silence: f32,
+) {
+    let mut batch_open = true;
+    let mut stream_open = true;
+    while batch_open || stream_open {
+        let next = tokio::select! {
+            biased;
+            job = batch_rx.recv(), if batch_open => NextJob::Batch(job),
+            job = stream_rx.recv(), if stream_open => NextJob::Stream(job),
+        };
+        let job = match next {
+            NextJob::Batch(Ok(job)) => job,
+            NextJob::Stream(Ok(job)) => job,
+            NextJob::Batch(Err(_)) => {
+                batch_open = false;
+                continue;
+            }
+            NextJob::Stream(Err(_)) => {
+                stream_open = false;
+                continue;
+            }
+        };
+        let result = transcribe_job_audio(transcriber, job.audio, &job.options, vad, vad_split_silence);
         let _ = job.reply.send(result);
     }
 }
 
+/// Applies energy-based VAD (when enabled) to skip silent payloads and split
+/// on long pauses before handing audio to the transcriber, then shifts each
+/// span's segment timestamps back into the original payload's timeline.
+/// The detected/pinned language is taken from the first voiced span.
+fn transcribe_job_audio(
+    transcriber: &mut Transcriber,
+    audio: Vec<f32>,
+    options: &TranscriptionOptions,
+    vad: VadMode,
+    vad_split_silence: f32,
+) -> Result<Transcription, String> {
+    if vad == VadMode::Off {
+        return transcriber.transcribe(audio, options).map_err(|err| err.to_string());
+    }
+
+    let spans = audio::split_spans_on_silence(&audio, vad_split_silence);
+    if spans.is_empty() {
+        return Ok(Transcription {
+            segments: Vec::new(),
+            language: None,
+        });
+    }
+
+    let mut segments = Vec::new();
+    let mut language = None;
+    for (start, end) in spans {
+        let offset_secs = start as f32 / audio::SAMPLE_RATE_HZ as f32;
+        let chunk = transcriber
+            .transcribe(audio[start..end].to_vec(), options)
+            .map_err(|err| err.to_string())?;
+        if language.is_none() {
+            language = chunk.language;
+        }
+        segments.extend(chunk.segments.into_iter().map(|segment| Segment {
+            start: segment.start + offset_secs,
+            end: segment.end + offset_secs,
+            text: segment.text,
+        }));
+    }
+    Ok(Transcription { segments, language })
+}
+
 async fn health_handler(State(state): State<AppState>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok",
         engine: state.config.engine.as_str().to_string(),
         model_path: state.config.model_path.clone(),
+        workers: state.config.workers.max(1),
+        ready_workers: state.ready_workers.load(Ordering::SeqCst),
     })
 }
 
 async fn transcribe_handler(
     State(state): State<AppState>,
+    Query(params): Query<TranscribeParams>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<axum::response::Response, (StatusCode, String)> {
     let started = std::time::Instant::now();
     let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
-    if content_type != Some("application/octet-stream") {
+    if !is_wav_content_type(content_type) && content_type != Some("application/octet-stream") {
         return Err((
             StatusCode::UNSUPPORTED_MEDIA_TYPE,
-            "Content-Type must be application/octet-stream".to_string(),
+            "Content-Type must be audio/wav, audio/x-wav, audio/wave, or application/octet-stream"
+                .to_string(),
         ));
     }
     if body.is_empty() {
         return Err((StatusCode::BAD_REQUEST, "Empty audio payload".to_string()));
     }
-    info!("Received PCM audio ({} bytes)", body.len());
-    let audio = audio::load_pcm_i16le_bytes(&body)
+    info!("Received audio ({} bytes, content-type {:?})", body.len(), content_type);
+    let audio = decode_request_audio(content_type, &body)
         .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
     if audio.is_empty() {
         return Err((StatusCode::BAD_REQUEST, "No audio samples found".to_string()));
     }
 
-    let text = state
+    let transcription = state
         .worker
-        .transcribe(audio)
+        .transcribe(audio, params.into_options())
         .await
         .map_err(|err| {
             let status = err.status_code();
@@ -201,7 +426,8 @@ async fn transcribe_handler(
         })?;
 
     let response = TranscribeResponse {
-        text,
+        text: join_segment_text(&transcription.segments),
+        language: transcription.language,
     };
 
     info!("Transcription completed in {:?}", started.elapsed());
@@ -211,3 +437,595 @@ async fn transcribe_handler(
         .insert(header::CONNECTION, HeaderValue::from_static("keep-alive"));
     Ok(response)
 }
+
+/// Whether `content_type` is one of the Content-Type values browsers and
+/// OpenAI SDK clients use for WAV uploads. Shared by `/transcribe`'s
+/// Content-Type guard and [`decode_request_audio`] so the two stay in sync.
+fn is_wav_content_type(content_type: Option<&str>) -> bool {
+    matches!(content_type, Some("audio/wav") | Some("audio/x-wav") | Some("audio/wave"))
+}
+
+/// Normalizes an uploaded payload to the 16 kHz mono `Vec<f32>` the engines
+/// expect: `audio/wav` (and common aliases) are parsed as a WAV container
+/// and resampled if needed, everything else is treated as headerless 16 kHz
+/// i16le PCM.
+fn decode_request_audio(content_type: Option<&str>, bytes: &[u8]) -> Result<Vec<f32>> {
+    if is_wav_content_type(content_type) {
+        let (samples, sample_rate) = audio::parse_wav(bytes)?;
+        Ok(audio::resample_to_16k(&samples, sample_rate))
+    } else {
+        audio::load_pcm_i16le_bytes(bytes)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum StreamMessage {
+    Partial { text: String },
+    Final { text: String },
+    Error { message: String },
+}
+
+async fn stream_handler(
+    State(state): State<AppState>,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    ws.on_upgrade(move |socket| handle_stream_socket(socket, state))
+}
+
+/// Drives one `/stream` connection: accumulates incoming i16le PCM frames in
+/// a rolling buffer, submits a window for transcription whenever the buffer,
+/// retained overlap included, reaches `CHUNK_SECONDS` of audio (retaining a
+/// short overlap so words aren't cut at the boundary), and emits a `partial` message per
+/// window. An `"eos"` text message, or — when VAD is enabled — a
+/// VAD-detected speech endpoint (trailing silence at least
+/// `vad_split_silence` seconds long), flushes the buffered audio, emits a
+/// `final` message, and resets state for the next utterance.
+async fn handle_stream_socket(mut socket: WebSocket, state: AppState) {
+    let window_bytes = audio::SAMPLES_PER_CHUNK * audio::BYTES_PER_SAMPLE;
+    let overlap_bytes = STREAM_WINDOW_OVERLAP_SECONDS * audio::SAMPLE_RATE_HZ * audio::BYTES_PER_SAMPLE;
+    let vad = state.config.vad;
+    let vad_split_silence = state.config.vad_split_silence;
+    let vad_split_silence_bytes =
+        (vad_split_silence * audio::SAMPLE_RATE_HZ as f32) as usize * audio::BYTES_PER_SAMPLE;
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut last_text = String::new();
+    // Carried across windows of the same utterance; reset on `final`/`eos`.
+    let mut vad_noise_floor: Option<f32> = None;
+
+    while let Some(Ok(message)) = socket.recv().await {
+        match message {
+            Message::Binary(bytes) => {
+                buffer.extend_from_slice(&bytes);
+
+                if vad != VadMode::Off {
+                    let (endpoint, floor) =
+                        detect_stream_endpoint(&buffer, vad_split_silence, vad_noise_floor);
+                    vad_noise_floor = Some(floor);
+                    if let Some(endpoint) = endpoint {
+                        if let Ok(text) = transcribe_stream_window(&state, &buffer[..endpoint]).await {
+                            last_text = text;
+                        }
+                        let final_message = StreamMessage::Final {
+                            text: std::mem::take(&mut last_text),
+                        };
+                        buffer.clear();
+                        vad_noise_floor = None;
+                        if send_stream_message(&mut socket, final_message).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                }
+
+                if buffer.len() < window_bytes {
+                    continue;
+                }
+                match transcribe_stream_window(&state, &buffer).await {
+                    Ok(text) => {
+                        last_text = text.clone();
+                        if send_stream_message(&mut socket, StreamMessage::Partial { text })
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        warn!("Streaming transcription failed: {}", err);
+                        let message = StreamMessage::Error {
+                            message: err.to_string(),
+                        };
+                        if send_stream_message(&mut socket, message).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                // Retain enough tail to cover `vad_split_silence`, not just the
+                // fixed `overlap_bytes`, so a longer-than-1s silence threshold
+                // still has trailing silence to measure after window rollover.
+                let retain_bytes = overlap_bytes.max(vad_split_silence_bytes);
+                retain_window_overlap(&mut buffer, retain_bytes);
+            }
+            Message::Text(text) if text == "eos" => {
+                if !buffer.is_empty() {
+                    if let Ok(text) = transcribe_stream_window(&state, &buffer).await {
+                        last_text = text;
+                    }
+                }
+                let final_message = StreamMessage::Final {
+                    text: std::mem::take(&mut last_text),
+                };
+                buffer.clear();
+                vad_noise_floor = None;
+                if send_stream_message(&mut socket, final_message).await.is_err() {
+                    break;
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+}
+
+/// Retains only the trailing `overlap_bytes` of `buffer` (the whole buffer
+/// if it's shorter), discarding the rest now that it's been transcribed —
+/// keeps a short overlap at the head of the next window so a word spoken
+/// right at the boundary isn't cut in half.
+fn retain_window_overlap(buffer: &mut Vec<u8>, overlap_bytes: usize) {
+    let keep_from = buffer.len().saturating_sub(overlap_bytes);
+    buffer.drain(..keep_from);
+}
+
+/// Looks for a VAD-detected end of utterance in the buffered i16le PCM:
+/// voiced spans followed by at least `vad_split_silence` seconds of trailing
+/// silence. Returns the byte offset right after the last voiced span (the
+/// audio to finalize on), or `None` while the speaker is still talking or
+/// hasn't said anything yet — alongside the noise floor at the end of
+/// `buffer`, which the caller carries into the next call over the same
+/// utterance (see [`audio::detect_voiced_regions_seeded`]).
+fn detect_stream_endpoint(
+    buffer: &[u8],
+    vad_split_silence: f32,
+    seed_floor: Option<f32>,
+) -> (Option<usize>, f32) {
+    let samples = match audio::load_pcm_i16le_bytes(buffer) {
+        Ok(samples) => samples,
+        Err(_) => return (None, seed_floor.unwrap_or(0.0)),
+    };
+    let (spans, floor) = audio::detect_voiced_regions_seeded(&samples, seed_floor);
+    let endpoint = spans.last().and_then(|&(_, last_voiced_end)| {
+        let trailing_silence_secs = (samples.len() - last_voiced_end) as f32 / audio::SAMPLE_RATE_HZ as f32;
+        (trailing_silence_secs >= vad_split_silence).then(|| last_voiced_end * audio::BYTES_PER_SAMPLE)
+    });
+    (endpoint, floor)
+}
+
+async fn transcribe_stream_window(state: &AppState, buffer: &[u8]) -> Result<String, WorkerError> {
+    let audio = audio::load_pcm_i16le_bytes(buffer)
+        .map_err(|err| WorkerError::Transcription(err.to_string()))?;
+    let transcription = state.worker.transcribe_stream(audio).await?;
+    Ok(join_segment_text(&transcription.segments))
+}
+
+async fn send_stream_message(
+    socket: &mut WebSocket,
+    message: StreamMessage,
+) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(&message).expect("StreamMessage always serializes");
+    socket.send(Message::Text(text)).await
+}
+
+#[derive(Debug, Serialize)]
+struct VerboseJsonSegment {
+    id: u32,
+    start: f32,
+    end: f32,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct VerboseJsonResponse {
+    task: &'static str,
+    language: Option<String>,
+    text: String,
+    segments: Vec<VerboseJsonSegment>,
+}
+
+/// OpenAI Whisper-API-compatible endpoint: accepts a `multipart/form-data`
+/// body with a `file` part plus `model`, `language`, `translate`,
+/// `temperature`, `beam_size`, and `response_format` fields, so existing
+/// OpenAI SDK clients can point at this server unchanged. `response_format`
+/// selects among `json` (default), `text`, `verbose_json`, `srt`, and `vtt`.
+async fn openai_transcriptions_handler(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<axum::response::Response, (StatusCode, String)> {
+    let mut audio_bytes: Option<Bytes> = None;
+    let mut file_content_type: Option<String> = None;
+    let mut response_format = "json".to_string();
+    let mut language: Option<String> = None;
+    let mut translate = false;
+    let mut temperature = 0.0;
+    let mut beam_size = 1;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?
+    {
+        let field_name = field.name().unwrap_or("").to_string();
+        match field_name.as_str() {
+            "file" => {
+                file_content_type = field.content_type().map(str::to_string);
+                audio_bytes = Some(
+                    field
+                        .bytes()
+                        .await
+                        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?,
+                );
+            }
+            "response_format" => {
+                response_format = read_multipart_text(field).await?;
+            }
+            "language" => {
+                language = Some(read_multipart_text(field).await?);
+            }
+            "translate" => {
+                translate = read_multipart_text(field)
+                    .await?
+                    .parse()
+                    .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid translate value".to_string()))?;
+            }
+            "temperature" => {
+                temperature = read_multipart_text(field)
+                    .await?
+                    .parse()
+                    .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid temperature value".to_string()))?;
+            }
+            "beam_size" => {
+                beam_size = read_multipart_text(field)
+                    .await?
+                    .parse()
+                    .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid beam_size value".to_string()))?;
+            }
+            // `model` is accepted for SDK compatibility, but this server
+            // only ever loads the one model it was started with.
+            _ => {
+                let _ = field.bytes().await;
+            }
+        }
+    }
+
+    let bytes = audio_bytes.ok_or((
+        StatusCode::BAD_REQUEST,
+        "Missing \"file\" part in multipart body".to_string(),
+    ))?;
+    if bytes.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Empty audio payload".to_string()));
+    }
+    if !is_wav_content_type(file_content_type.as_deref()) && file_content_type.as_deref() != Some("application/octet-stream") {
+        return Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "file part Content-Type must be audio/wav, audio/x-wav, audio/wave, or application/octet-stream"
+                .to_string(),
+        ));
+    }
+
+    let audio = decode_request_audio(file_content_type.as_deref(), &bytes)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    if audio.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "No audio samples found".to_string()));
+    }
+
+    let options = TranscriptionOptions {
+        language: language.filter(|lang| lang != "auto"),
+        translate,
+        temperature,
+        beam_size: beam_size.max(1),
+    };
+    let transcription = state.worker.transcribe(audio, options).await.map_err(|err| {
+        let status = err.status_code();
+        warn!("Transcription failed: {}", err);
+        (status, err.to_string())
+    })?;
+
+    render_transcription_response(&response_format, &transcription)
+}
+
+async fn read_multipart_text(
+    field: axum::extract::multipart::Field<'_>,
+) -> Result<String, (StatusCode, String)> {
+    field
+        .text()
+        .await
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))
+}
+
+fn render_transcription_response(
+    response_format: &str,
+    transcription: &Transcription,
+) -> Result<axum::response::Response, (StatusCode, String)> {
+    let segments = &transcription.segments;
+    match response_format {
+        "json" => Ok(Json(TranscribeResponse {
+            text: join_segment_text(segments),
+            language: transcription.language.clone(),
+        })
+        .into_response()),
+        "text" => Ok(join_segment_text(segments).into_response()),
+        "verbose_json" => Ok(Json(VerboseJsonResponse {
+            task: "transcribe",
+            language: transcription.language.clone(),
+            text: join_segment_text(segments),
+            segments: segments
+                .iter()
+                .enumerate()
+                .map(|(id, segment)| VerboseJsonSegment {
+                    id: id as u32,
+                    start: segment.start,
+                    end: segment.end,
+                    text: segment.text.trim().to_string(),
+                })
+                .collect(),
+        })
+        .into_response()),
+        "srt" => Ok(render_srt(segments).into_response()),
+        "vtt" => Ok(render_vtt(segments).into_response()),
+        other => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported response_format: {other}"),
+        )),
+    }
+}
+
+fn render_srt(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        out.push_str(&format!("{}\n", i + 1));
+        out.push_str(&format!(
+            "{} --> {}\n",
+            format_srt_timestamp(segment.start),
+            format_srt_timestamp(segment.end)
+        ));
+        out.push_str(segment.text.trim());
+        out.push_str("\n\n");
+    }
+    out
+}
+
+fn render_vtt(segments: &[Segment]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for segment in segments {
+        out.push_str(&format!(
+            "{} --> {}\n",
+            format_vtt_timestamp(segment.start),
+            format_vtt_timestamp(segment.end)
+        ));
+        out.push_str(segment.text.trim());
+        out.push_str("\n\n");
+    }
+    out
+}
+
+fn format_srt_timestamp(seconds: f32) -> String {
+    format_timestamp(seconds, ',')
+}
+
+fn format_vtt_timestamp(seconds: f32) -> String {
+    format_timestamp(seconds, '.')
+}
+
+fn format_timestamp(seconds: f32, millis_separator: char) -> String {
+    let total_millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    let hours = total_millis / 3_600_000;
+    let minutes = (total_millis % 3_600_000) / 60_000;
+    let secs = (total_millis % 60_000) / 1000;
+    let millis = total_millis % 1000;
+    format!("{hours:02}:{minutes:02}:{secs:02}{millis_separator}{millis:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_wav_pcm16(sample_rate: u32, channels: u16, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let byte_rate = sample_rate * channels as u32 * 2;
+        let block_align = channels * 2;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // WAVE_FORMAT_PCM
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&byte_rate.to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    fn segment(start: f32, end: f32, text: &str) -> Segment {
+        Segment {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn format_timestamp_renders_hh_mm_ss_with_given_separator() {
+        assert_eq!(format_timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(format_timestamp(3_661.25, '.'), "01:01:01.250");
+    }
+
+    #[test]
+    fn format_timestamp_clamps_negative_seconds_to_zero() {
+        assert_eq!(format_timestamp(-1.0, ','), "00:00:00,000");
+    }
+
+    #[test]
+    fn render_srt_numbers_cues_and_uses_comma_millis() {
+        let segments = vec![segment(0.0, 1.5, " Hello "), segment(1.5, 3.0, "world")];
+        let srt = render_srt(&segments);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:01,500\nHello\n\n2\n00:00:01,500 --> 00:00:03,000\nworld\n\n"
+        );
+    }
+
+    #[test]
+    fn render_vtt_has_header_and_uses_dot_millis() {
+        let segments = vec![segment(0.0, 1.5, "Hello")];
+        let vtt = render_vtt(&segments);
+        assert_eq!(vtt, "WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nHello\n\n");
+    }
+
+    #[test]
+    fn render_srt_of_empty_segments_is_empty() {
+        assert_eq!(render_srt(&[]), "");
+    }
+
+    #[test]
+    fn decode_request_audio_treats_octet_stream_as_raw_pcm() {
+        let bytes = 0i16.to_le_bytes().repeat(4);
+        let audio = decode_request_audio(Some("application/octet-stream"), &bytes).unwrap();
+        assert_eq!(audio.len(), 4);
+    }
+
+    #[test]
+    fn decode_request_audio_parses_and_resamples_wav() {
+        let wav = build_wav_pcm16(8_000, 1, &[0, i16::MAX / 2]);
+        let audio = decode_request_audio(Some("audio/wav"), &wav).unwrap();
+        // 8 kHz -> 16 kHz doubles the sample count (1:2 ratio).
+        assert_eq!(audio.len(), 4);
+    }
+
+    #[test]
+    fn decode_request_audio_rejects_malformed_wav() {
+        let err = decode_request_audio(Some("audio/wave"), &[0u8; 4]).unwrap_err();
+        assert!(err.to_string().contains("RIFF"));
+    }
+
+    #[test]
+    fn is_wav_content_type_accepts_common_aliases() {
+        assert!(is_wav_content_type(Some("audio/wav")));
+        assert!(is_wav_content_type(Some("audio/x-wav")));
+        assert!(is_wav_content_type(Some("audio/wave")));
+        assert!(!is_wav_content_type(Some("application/octet-stream")));
+        assert!(!is_wav_content_type(None));
+    }
+
+    #[test]
+    fn retain_window_overlap_keeps_only_the_tail() {
+        let mut buffer: Vec<u8> = (0..10u8).collect();
+        retain_window_overlap(&mut buffer, 4);
+        assert_eq!(buffer, vec![6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn retain_window_overlap_keeps_whole_buffer_when_shorter_than_overlap() {
+        let mut buffer: Vec<u8> = vec![1, 2, 3];
+        retain_window_overlap(&mut buffer, 10);
+        assert_eq!(buffer, vec![1, 2, 3]);
+    }
+
+    fn silence_bytes(duration_secs: f32) -> Vec<u8> {
+        let n = (audio::SAMPLE_RATE_HZ as f32 * duration_secs) as usize;
+        vec![0u8; n * audio::BYTES_PER_SAMPLE]
+    }
+
+    fn tone_bytes(freq_hz: f32, amplitude: f32, duration_secs: f32) -> Vec<u8> {
+        let n = (audio::SAMPLE_RATE_HZ as f32 * duration_secs) as usize;
+        let mut bytes = Vec::with_capacity(n * audio::BYTES_PER_SAMPLE);
+        for i in 0..n {
+            let t = i as f32 / audio::SAMPLE_RATE_HZ as f32;
+            let sample = amplitude * (2.0 * std::f32::consts::PI * freq_hz * t).sin();
+            let value = (sample * i16::MAX as f32) as i16;
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn detect_stream_endpoint_fires_after_long_enough_trailing_silence() {
+        let mut buffer = silence_bytes(0.5);
+        buffer.extend(tone_bytes(220.0, 0.8, 1.0));
+        buffer.extend(silence_bytes(1.5));
+
+        let (endpoint, _) = detect_stream_endpoint(&buffer, 1.0, None);
+        assert!(endpoint.is_some());
+        assert!(endpoint.unwrap() < buffer.len());
+    }
+
+    #[test]
+    fn detect_stream_endpoint_is_none_while_still_talking() {
+        let mut buffer = silence_bytes(0.5);
+        buffer.extend(tone_bytes(220.0, 0.8, 1.0));
+        buffer.extend(silence_bytes(0.1));
+
+        assert_eq!(detect_stream_endpoint(&buffer, 1.0, None).0, None);
+    }
+
+    #[test]
+    fn detect_stream_endpoint_is_none_for_pure_silence() {
+        let buffer = silence_bytes(1.0);
+        assert_eq!(detect_stream_endpoint(&buffer, 0.5, None).0, None);
+    }
+
+    #[test]
+    fn detect_stream_endpoint_fires_after_a_retained_mid_utterance_tail() {
+        // The floor from a real-silence-led first window, carried across a
+        // rollover whose own head is mid-speech, must still catch trailing
+        // silence once the speaker stops.
+        let vad_split_silence = 1.0;
+        let mut first_window = silence_bytes(0.5);
+        first_window.extend(tone_bytes(220.0, 0.8, 2.5));
+        let (endpoint, seed_floor) = detect_stream_endpoint(&first_window, vad_split_silence, None);
+        assert_eq!(endpoint, None);
+
+        let mut retained_tail = tone_bytes(220.0, 0.8, 1.5);
+        retained_tail.extend(silence_bytes(1.5));
+
+        let (endpoint, _) = detect_stream_endpoint(&retained_tail, vad_split_silence, Some(seed_floor));
+        assert!(endpoint.is_some());
+        assert!(endpoint.unwrap() < retained_tail.len());
+
+        // Without the carried floor this never fires — the bug this guards.
+        let (blind_endpoint, _) = detect_stream_endpoint(&retained_tail, vad_split_silence, None);
+        assert_eq!(blind_endpoint, None);
+    }
+
+    #[test]
+    fn stream_buffer_stays_bounded_across_repeated_windows_under_vad() {
+        // Mirrors handle_stream_socket's per-window bookkeeping for a long,
+        // continuous utterance under VAD: every window crossing must retain
+        // enough tail to cover `vad_split_silence`, not let the buffer grow
+        // forever just because no endpoint has fired yet.
+        let window_bytes = audio::SAMPLES_PER_CHUNK * audio::BYTES_PER_SAMPLE;
+        let overlap_bytes = STREAM_WINDOW_OVERLAP_SECONDS * audio::SAMPLE_RATE_HZ * audio::BYTES_PER_SAMPLE;
+        let vad_split_silence = 2.0_f32;
+        let vad_split_silence_bytes =
+            (vad_split_silence * audio::SAMPLE_RATE_HZ as f32) as usize * audio::BYTES_PER_SAMPLE;
+        let retain_bytes = overlap_bytes.max(vad_split_silence_bytes);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut noise_floor: Option<f32> = None;
+        for _ in 0..10 {
+            buffer.extend(tone_bytes(220.0, 0.8, audio::SAMPLES_PER_CHUNK as f32 / audio::SAMPLE_RATE_HZ as f32));
+            let (endpoint, floor) = detect_stream_endpoint(&buffer, vad_split_silence, noise_floor);
+            noise_floor = Some(floor);
+            assert!(endpoint.is_none());
+            retain_window_overlap(&mut buffer, retain_bytes);
+            assert!(buffer.len() <= retain_bytes + window_bytes);
+        }
+    }
+}
@@ -18,6 +18,80 @@ pub struct Transcriber {
     engine: LoadedEngine,
 }
 
+/// A single transcribed span with start/end timestamps in seconds, as needed
+/// to render SRT/VTT captions or `verbose_json` responses.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+}
+
+/// Trims each segment's text and joins them with a single space — the same
+/// shape the flat-text API produced before segments were introduced.
+pub fn join_segment_text(segments: &[Segment]) -> String {
+    let mut text = String::new();
+    for segment in segments {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(segment.text.trim());
+    }
+    text
+}
+
+/// The result of one [`Transcriber::transcribe`] call.
+#[derive(Debug, Clone)]
+pub struct Transcription {
+    pub segments: Vec<Segment>,
+    /// ISO-639-1 code Whisper detected (or was pinned to); always `"en"` for
+    /// the English-only Parakeet engine. `None` when no language was determined.
+    pub language: Option<String>,
+}
+
+/// Per-request decoding knobs, threaded from the HTTP layer down to
+/// Whisper's [`FullParams`]. The Parakeet engine ignores them.
+#[derive(Debug, Clone)]
+pub struct TranscriptionOptions {
+    /// ISO-639-1 code to pin the language to, or `None` to auto-detect.
+    pub language: Option<String>,
+    /// Translate the result to English (Whisper's `task=translate`).
+    pub translate: bool,
+    pub temperature: f32,
+    /// `1` keeps greedy decoding; anything higher switches to beam search.
+    pub beam_size: usize,
+}
+
+impl Default for TranscriptionOptions {
+    fn default() -> Self {
+        Self {
+            language: None,
+            translate: false,
+            temperature: 0.0,
+            beam_size: 1,
+        }
+    }
+}
+
+/// Maps `beam_size` to the `whisper_rs` sampling strategy: greedy decoding
+/// at `1` (the common case), beam search above it. `patience: -1.0` means
+/// "no early-stopping patience heuristic", matching whisper.cpp's default.
+fn sampling_strategy(beam_size: usize) -> SamplingStrategy {
+    if beam_size > 1 {
+        SamplingStrategy::BeamSearch {
+            beam_size: beam_size as i32,
+            patience: -1.0,
+        }
+    } else {
+        SamplingStrategy::Greedy { best_of: 1 }
+    }
+}
+
+/// `None`/`"auto"` both mean "let Whisper auto-detect the language".
+fn normalize_language(language: &Option<String>) -> Option<&str> {
+    language.as_deref().filter(|lang| *lang != "auto")
+}
+
 impl Transcriber {
     pub fn new(engine: EngineKind, model_path: impl AsRef<Path>) -> Result<Self> {
         let path = model_path.as_ref();
@@ -53,16 +127,22 @@ impl Transcriber {
         Ok(Self { engine })
     }
 
-    pub fn transcribe(&mut self, audio: Vec<f32>) -> Result<String> {
+    pub fn transcribe(
+        &mut self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<Transcription> {
         match &mut self.engine {
             LoadedEngine::Whisper(context) => {
                 let mut state = context
                     .create_state()
                     .map_err(|e| anyhow!("Failed to create state: {}", e))?;
 
-                let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-                params.set_language(None);
-                params.set_translate(false);
+                let strategy = sampling_strategy(options.beam_size);
+                let mut params = FullParams::new(strategy);
+                params.set_language(normalize_language(&options.language));
+                params.set_translate(options.translate);
+                params.set_temperature(options.temperature);
                 params.set_print_special(false);
                 params.set_print_progress(false);
                 params.set_print_realtime(false);
@@ -76,16 +156,31 @@ impl Transcriber {
                     .full_n_segments()
                     .map_err(|e| anyhow!("Failed to get segments: {}", e))?;
 
-                let mut text = String::new();
+                let mut segments = Vec::with_capacity(num_segments as usize);
                 for i in 0..num_segments {
-                    let segment = state
+                    let text = state
                         .full_get_segment_text(i)
                         .map_err(|e| anyhow!("Failed to get segment: {}", e))?;
-                    text.push_str(&segment);
-                    text.push(' ');
+                    // Whisper reports segment bounds in 10 ms ticks.
+                    let start = state
+                        .full_get_segment_t0(i)
+                        .map_err(|e| anyhow!("Failed to get segment start: {}", e))?
+                        as f32
+                        / 100.0;
+                    let end = state
+                        .full_get_segment_t1(i)
+                        .map_err(|e| anyhow!("Failed to get segment end: {}", e))?
+                        as f32
+                        / 100.0;
+                    segments.push(Segment { start, end, text });
                 }
 
-                Ok(text.trim().to_string())
+                let detected_lang_id = state
+                    .full_lang_id()
+                    .map_err(|e| anyhow!("Failed to get detected language: {}", e))?;
+                let language = whisper_rs::get_lang_str(detected_lang_id).map(str::to_string);
+
+                Ok(Transcription { segments, language })
             }
             LoadedEngine::Parakeet(engine) => {
                 let params = ParakeetInferenceParams {
@@ -94,8 +189,53 @@ impl Transcriber {
                 let result = engine
                     .transcribe_samples(audio, Some(params))
                     .map_err(|e| anyhow!("Parakeet transcription failed: {}", e))?;
-                Ok(result.text)
+
+                let segments = result
+                    .segments
+                    .into_iter()
+                    .map(|segment| Segment {
+                        start: segment.start,
+                        end: segment.end,
+                        text: segment.text,
+                    })
+                    .collect();
+                // Parakeet is English-only; there's no language field to detect.
+                Ok(Transcription {
+                    segments,
+                    language: Some("en".to_string()),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling_strategy_is_greedy_at_beam_size_one() {
+        match sampling_strategy(1) {
+            SamplingStrategy::Greedy { best_of } => assert_eq!(best_of, 1),
+            other => panic!("expected greedy strategy, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn sampling_strategy_switches_to_beam_search_above_one() {
+        match sampling_strategy(4) {
+            SamplingStrategy::BeamSearch { beam_size, patience } => {
+                assert_eq!(beam_size, 4);
+                assert_eq!(patience, -1.0);
             }
+            other => panic!("expected beam search strategy, got {other:?}"),
         }
     }
+
+    #[test]
+    fn normalize_language_treats_auto_as_detect() {
+        assert_eq!(normalize_language(&Some("auto".to_string())), None);
+        assert_eq!(normalize_language(&None), None);
+        assert_eq!(normalize_language(&Some("en".to_string())), Some("en"));
+    }
 }